@@ -0,0 +1,96 @@
+use alloc::string::String;
+use core::fmt;
+
+use miden_objects::{
+    accounts::AccountId, assembly::AssemblyError, notes::NoteError,
+    utils::serde::DeserializationError, Digest, TransactionOutputError,
+};
+
+// TRANSACTION KERNEL ERROR
+// ================================================================================================
+
+/// Errors that can occur while sanitizing transaction inputs, verifying a transaction kernel, or
+/// aggregating the outputs of a batch of transactions.
+#[derive(Debug)]
+pub enum TransactionKernelError {
+    /// `init_acct_hash` is not [miden_objects::EMPTY_WORD] for a new account, or is
+    /// [miden_objects::EMPTY_WORD] for an existing one.
+    InvalidInitialAccountHash(Digest),
+    /// The supplied input notes could not be assembled into an `InputNotes` collection.
+    InvalidInputNotes(NoteError),
+    /// `input_notes_hash` does not match the commitment computed over the supplied input notes.
+    InputNotesCommitmentMismatch { expected: Digest, actual: Digest },
+    /// Two parts of an aggregated batch reported a final account hash for the same account.
+    DuplicateFinalAccountHash(AccountId),
+    /// Parsing the outputs of one part of an aggregated batch failed, or the output notes
+    /// combined across the whole batch could not form a valid `OutputNotes` collection.
+    OutputParsingFailed(TransactionOutputError),
+    /// The embedded kernel program could not be deserialized.
+    KernelDeserializationFailed(DeserializationError),
+    /// The embedded kernel program failed to compile, or one of its procedures did not resolve
+    /// against the loaded libraries.
+    KernelAssemblyFailed(AssemblyError),
+}
+
+impl fmt::Display for TransactionKernelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidInitialAccountHash(hash) => {
+                write!(f, "invalid initial account hash: {hash}")
+            },
+            Self::InvalidInputNotes(err) => write!(f, "invalid input notes: {err}"),
+            Self::InputNotesCommitmentMismatch { expected, actual } => {
+                write!(f, "input notes commitment mismatch: expected {expected}, got {actual}")
+            },
+            Self::DuplicateFinalAccountHash(account_id) => {
+                write!(f, "duplicate final account hash reported for account {account_id}")
+            },
+            Self::OutputParsingFailed(err) => {
+                write!(f, "failed to parse transaction outputs: {err}")
+            },
+            Self::KernelDeserializationFailed(err) => {
+                write!(f, "failed to deserialize transaction kernel: {err}")
+            },
+            Self::KernelAssemblyFailed(err) => {
+                write!(f, "failed to assemble transaction kernel: {err}")
+            },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TransactionKernelError {}
+
+// TRANSACTION EVENT PARSING ERROR
+// ================================================================================================
+
+/// Error returned when a raw VM event emitted by the transaction kernel cannot be parsed into a
+/// [super::TransactionEvent].
+#[derive(Debug)]
+pub struct TransactionEventParsingError(pub String);
+
+impl fmt::Display for TransactionEventParsingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to parse transaction event: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TransactionEventParsingError {}
+
+// TRANSACTION TRACE PARSING ERROR
+// ================================================================================================
+
+/// Error returned when a raw VM trace emitted by the transaction kernel cannot be parsed into a
+/// [super::TransactionTrace].
+#[derive(Debug)]
+pub struct TransactionTraceParsingError(pub String);
+
+impl fmt::Display for TransactionTraceParsingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to parse transaction trace: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TransactionTraceParsingError {}
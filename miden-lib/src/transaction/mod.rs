@@ -1,9 +1,11 @@
-use alloc::{string::ToString, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeMap, string::ToString, vec::Vec};
+
+use once_cell::race::OnceBox;
 
 use miden_objects::{
     accounts::AccountId,
     assembly::{Assembler, AssemblyContext, ProgramAst},
-    transaction::{OutputNote, OutputNotes, TransactionOutputs},
+    transaction::{InputNote, InputNotes, OutputNote, OutputNotes, TransactionOutputs},
     utils::{group_slice_elements, serde::DeserializationError},
     vm::{AdviceMap, ProgramInfo, StackInputs, StackOutputs},
     Digest, Felt, TransactionOutputError, Word, EMPTY_WORD,
@@ -30,6 +32,115 @@ pub use errors::{
     TransactionEventParsingError, TransactionKernelError, TransactionTraceParsingError,
 };
 
+// KERNEL VERSION
+// ================================================================================================
+
+/// Identifies a specific, immutable revision of the transaction kernel.
+///
+/// A transaction is proven against the kernel version that was canonical when it was built, so
+/// pinning a version lets a prover reproduce the exact kernel used for a historical block even
+/// after newer kernel versions have been introduced. New variants must be appended in ascending
+/// order and roll out disabled-by-default: [KernelVersion::latest] only advances once a version
+/// is ready to become canonical for new transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KernelVersion {
+    V0,
+}
+
+impl KernelVersion {
+    /// Returns the kernel version that is canonical for new transactions.
+    pub const fn latest() -> Self {
+        Self::V0
+    }
+
+    /// Returns all known kernel versions, oldest first.
+    const fn all() -> &'static [Self] {
+        &[Self::V0]
+    }
+
+    /// Returns the [ProgramInfo] cache cell dedicated to this version.
+    ///
+    /// Every variant owns exactly one static cell via this match, so a new variant that isn't
+    /// wired up here fails to compile instead of aliasing another version's cache or panicking
+    /// on an out-of-bounds lookup at runtime the way an array indexed by a hand-maintained count
+    /// would.
+    fn program_info_cache(self) -> &'static OnceBox<ProgramInfo> {
+        static V0_CACHE: OnceBox<ProgramInfo> = OnceBox::new();
+        match self {
+            Self::V0 => &V0_CACHE,
+        }
+    }
+}
+
+impl Default for KernelVersion {
+    fn default() -> Self {
+        Self::latest()
+    }
+}
+
+// SANITIZED TRANSACTION INPUTS
+// ================================================================================================
+
+/// A set of transaction inputs that has been validated and is safe to build a kernel input stack
+/// from.
+///
+/// Constructing this type is the only sanctioned way of turning loose caller-supplied values into
+/// something [TransactionKernel::build_input_stack] will accept, mirroring how transaction
+/// sanitization happens up front rather than being left to whichever prover assembles the stack.
+pub struct SanitizedTransactionInputs {
+    account_id: AccountId,
+    init_acct_hash: Digest,
+    input_notes_hash: Digest,
+    block_hash: Digest,
+}
+
+impl SanitizedTransactionInputs {
+    /// Validates the raw transaction inputs and wraps them for use by
+    /// [TransactionKernel::build_input_stack].
+    ///
+    /// `is_new_account` must reflect whether `account_id` has any prior on-chain state; this
+    /// cannot be derived from the other arguments and so must be supplied by the caller.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - `init_acct_hash` is not [EMPTY_WORD] for a new account, or is [EMPTY_WORD] for an
+    ///   existing one.
+    /// - `input_notes_hash` does not match the commitment computed over `input_notes`.
+    /// - `input_notes` is invalid, e.g. it contains a duplicate nullifier.
+    ///
+    /// `account_id` is already a well-formed, construction-validated [AccountId], so there is
+    /// nothing left to validate about it here.
+    pub fn new(
+        account_id: AccountId,
+        init_acct_hash: Digest,
+        input_notes_hash: Digest,
+        block_hash: Digest,
+        input_notes: &[InputNote],
+        is_new_account: bool,
+    ) -> Result<Self, TransactionKernelError> {
+        let is_empty_hash = init_acct_hash == Digest::from(EMPTY_WORD);
+        if is_new_account && !is_empty_hash {
+            return Err(TransactionKernelError::InvalidInitialAccountHash(init_acct_hash));
+        }
+        if !is_new_account && is_empty_hash {
+            return Err(TransactionKernelError::InvalidInitialAccountHash(init_acct_hash));
+        }
+
+        // `InputNotes::new` already rejects a duplicate or out-of-range nullifier, so there is
+        // nothing left to re-check here once it succeeds.
+        let notes = InputNotes::new(input_notes.to_vec())
+            .map_err(TransactionKernelError::InvalidInputNotes)?;
+        if notes.commitment() != input_notes_hash {
+            return Err(TransactionKernelError::InputNotesCommitmentMismatch {
+                expected: input_notes_hash,
+                actual: notes.commitment(),
+            });
+        }
+
+        Ok(Self { account_id, init_acct_hash, input_notes_hash, block_hash })
+    }
+}
+
 // TRANSACTION KERNEL
 // ================================================================================================
 
@@ -39,51 +150,134 @@ impl TransactionKernel {
     // KERNEL SOURCE CODE
     // --------------------------------------------------------------------------------------------
 
-    /// Returns MASM source code which encodes the transaction kernel system procedures.
-    pub fn kernel() -> &'static str {
-        include_str!("../../asm/kernels/transaction/api.masm")
+    /// Returns MASM source code which encodes the transaction kernel system procedures for the
+    /// given kernel version.
+    pub fn kernel(version: KernelVersion) -> &'static str {
+        match version {
+            KernelVersion::V0 => include_str!("../../asm/kernels/transaction/api.masm"),
+        }
     }
 
-    /// Returns an AST of the transaction kernel executable program.
+    /// Returns the raw bytes of the compiled transaction kernel executable program for the given
+    /// kernel version.
+    fn main_bytes(version: KernelVersion) -> &'static [u8] {
+        match version {
+            KernelVersion::V0 => {
+                include_bytes!(concat!(env!("OUT_DIR"), "/assets/kernels/transaction.masb"))
+            },
+        }
+    }
+
+    /// Returns an AST of the transaction kernel executable program for the given kernel version.
     ///
     /// # Errors
     /// Returns an error if deserialization of the binary fails.
-    pub fn main() -> Result<ProgramAst, DeserializationError> {
-        let kernel_bytes =
-            include_bytes!(concat!(env!("OUT_DIR"), "/assets/kernels/transaction.masb"));
-        ProgramAst::from_bytes(kernel_bytes)
+    pub fn main(version: KernelVersion) -> Result<ProgramAst, DeserializationError> {
+        ProgramAst::from_bytes(Self::main_bytes(version))
     }
 
-    /// Returns [ProgramInfo] for the transaction kernel executable program.
+    /// Returns [ProgramInfo] for the transaction kernel executable program of the given kernel
+    /// version.
     ///
     /// # Panics
-    /// Panics if the transaction kernel source is not well-formed.
-    pub fn program_info() -> ProgramInfo {
-        // TODO: construct kernel_main and kernel using lazy static or at build time
-        let assembler = Self::assembler();
-        let main_ast = TransactionKernel::main().expect("main is well formed");
+    /// Panics if the transaction kernel source is not well-formed. Hosts that need to validate a
+    /// custom or upgraded kernel without risking a crash should call [Self::verify_kernel]
+    /// instead.
+    pub fn program_info(version: KernelVersion) -> ProgramInfo {
+        Self::cached_program_info(version)
+            .expect("transaction kernel is well-formed")
+            .clone()
+    }
+
+    /// Returns the cached [ProgramInfo] for the given version, assembling and verifying the
+    /// kernel the first time it is requested.
+    fn cached_program_info(
+        version: KernelVersion,
+    ) -> Result<&'static ProgramInfo, TransactionKernelError> {
+        version
+            .program_info_cache()
+            .get_or_try_init(|| Self::verify_kernel(version).map(Box::new))
+    }
+
+    /// Verifies that the transaction kernel of the given version is well-formed and returns its
+    /// [ProgramInfo].
+    ///
+    /// This deserializes [Self::main] and compiles it against an assembler loaded with the
+    /// kernel, [MidenLib] and [StdLibrary], which fails if any procedure referenced by
+    /// `api.masm` does not resolve against those libraries.
+    ///
+    /// This does *not* cross-check the resulting kernel's exported procedure count against a
+    /// separately declared count: the compiled kernel is the only source of truth for that
+    /// number, and comparing it against a second, hand-maintained constant would only
+    /// reintroduce a value that can silently drift from the kernel it is supposed to describe.
+    /// The procedure count placed on the input stack by [Self::build_input_stack] is always
+    /// read directly from this same compiled kernel, so the two can never disagree.
+    ///
+    /// Use this at startup to validate a custom or upgraded kernel; [Self::program_info] wraps
+    /// this and panics on failure for callers that can assume a well-formed kernel.
+    ///
+    /// # Errors
+    /// Returns an error if the kernel fails to deserialize or fails to compile.
+    pub fn verify_kernel(version: KernelVersion) -> Result<ProgramInfo, TransactionKernelError> {
+        let assembler = Assembler::default()
+            .with_library(&MidenLib::default())
+            .and_then(|assembler| assembler.with_library(&StdLibrary::default()))
+            .and_then(|assembler| assembler.with_kernel(Self::kernel(version)))
+            .map_err(TransactionKernelError::KernelAssemblyFailed)?;
+        let main_ast =
+            Self::main(version).map_err(TransactionKernelError::KernelDeserializationFailed)?;
         let kernel_main = assembler
             .compile_in_context(&main_ast, &mut AssemblyContext::for_program(Some(&main_ast)))
-            .expect("main is well formed");
+            .map_err(TransactionKernelError::KernelAssemblyFailed)?;
 
-        ProgramInfo::new(kernel_main.hash(), assembler.kernel().clone())
+        Ok(ProgramInfo::new(kernel_main.hash(), assembler.kernel().clone()))
+    }
+
+    // KERNEL REGISTRY
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns every known kernel version together with its `(source, masb, ProgramInfo)` triple,
+    /// ordered from oldest to newest.
+    ///
+    /// This lets a host enumerate every kernel a client may still be proving against, e.g. to
+    /// validate a historical transaction without hard-coding which version was canonical at the
+    /// time.
+    pub fn registry() -> Vec<(KernelVersion, &'static str, &'static [u8], ProgramInfo)> {
+        KernelVersion::all()
+            .iter()
+            .map(|&version| {
+                let source = Self::kernel(version);
+                let masb = Self::main_bytes(version);
+                let program_info = Self::program_info(version);
+                (version, source, masb, program_info)
+            })
+            .collect()
     }
 
     // ASSEMBLER CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
 
-    /// Returns a new Miden assembler instantiated with the transaction kernel and loaded with the
-    /// Miden stdlib as well as with midenlib.
-    pub fn assembler() -> Assembler {
+    /// Returns a new Miden assembler instantiated with the transaction kernel of the given
+    /// version and loaded with the Miden stdlib as well as with midenlib.
+    pub fn assembler(version: KernelVersion) -> Assembler {
         Assembler::default()
             .with_library(&MidenLib::default())
             .expect("failed to load miden-lib")
             .with_library(&StdLibrary::default())
             .expect("failed to load std-lib")
-            .with_kernel(Self::kernel())
+            .with_kernel(Self::kernel(version))
             .expect("kernel must be well formed")
     }
 
+    /// Returns the number of procedures exported by the kernel of the given version, and the hash
+    /// of the kernel as a whole.
+    fn kernel_info(version: KernelVersion) -> (usize, Digest) {
+        let program_info = Self::cached_program_info(version)
+            .expect("transaction kernel is well-formed");
+        let kernel = program_info.kernel();
+        (kernel.proc_hashes().len(), kernel.hash())
+    }
+
     // STACK INPUTS / OUTPUTS
     // --------------------------------------------------------------------------------------------
 
@@ -110,21 +304,20 @@ impl TransactionKernel {
     /// - kernel_procs_len, number of the procedures in the used kernel.
     /// - KERNEL_HASH, hash of the entire kernel.
     pub fn build_input_stack(
-        acct_id: AccountId,
-        init_acct_hash: Digest,
-        input_notes_hash: Digest,
-        block_hash: Digest,
-        kernel: (usize, Digest),
+        version: KernelVersion,
+        inputs: SanitizedTransactionInputs,
     ) -> StackInputs {
+        let (kernel_procs_len, kernel_hash) = Self::kernel_info(version);
+
         // Note: Must be kept in sync with the transaction's kernel prepare_transaction procedure
-        let mut inputs: Vec<Felt> = Vec::with_capacity(18);
-        inputs.extend(kernel.1);
-        inputs.push(Felt::from(kernel.0 as u16));
-        inputs.extend(input_notes_hash);
-        inputs.extend_from_slice(init_acct_hash.as_elements());
-        inputs.push(acct_id.into());
-        inputs.extend_from_slice(block_hash.as_elements());
-        StackInputs::new(inputs)
+        let mut stack_inputs: Vec<Felt> = Vec::with_capacity(18);
+        stack_inputs.extend(kernel_hash);
+        stack_inputs.push(Felt::from(kernel_procs_len as u16));
+        stack_inputs.extend(inputs.input_notes_hash);
+        stack_inputs.extend_from_slice(inputs.init_acct_hash.as_elements());
+        stack_inputs.push(inputs.account_id.into());
+        stack_inputs.extend_from_slice(inputs.block_hash.as_elements());
+        StackInputs::new(stack_inputs)
             .map_err(|e| e.to_string())
             .expect("Invalid stack input")
     }
@@ -229,4 +422,214 @@ impl TransactionKernel {
 
         Ok(TransactionOutputs { account, output_notes })
     }
+
+    // BATCH AGGREGATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Merges the outputs of many individually-proven transactions into a single aggregate
+    /// commitment, mirroring how an aggregated transaction body combines multiple transaction
+    /// kernels into one.
+    ///
+    /// Each `(StackOutputs, AdviceMap, Vec<OutputNote>)` triple in `parts` is parsed with
+    /// [Self::from_transaction_parts]; the resulting output notes are concatenated into a single
+    /// [OutputNotes] and the per-account final hashes are collected into an ordered map so that
+    /// every individual account-state transition can still be verified independently.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Any part fails to parse via [Self::from_transaction_parts].
+    /// - The notes combined across every part contain a duplicate note ID, or exceed
+    ///   [OutputNotes]'s per-transaction `MAX_OUTPUT_NOTES` cap applied to the concatenated
+    ///   batch (that cap is not itself a separately-defined whole-batch limit).
+    /// - Two parts report a final hash for the same account.
+    pub fn aggregate_outputs(
+        parts: &[(StackOutputs, AdviceMap, Vec<OutputNote>)],
+    ) -> Result<AggregatedTransactionOutputs, TransactionKernelError> {
+        let mut all_output_notes: Vec<OutputNote> = Vec::new();
+        let mut final_account_hashes: BTreeMap<AccountId, Digest> = BTreeMap::new();
+
+        for (stack, adv_map, output_notes) in parts {
+            let outputs = Self::from_transaction_parts(stack, adv_map, output_notes.clone())
+                .map_err(TransactionKernelError::OutputParsingFailed)?;
+
+            let account_id = outputs.account.id();
+            let account_hash = outputs.account.hash();
+            Self::merge_final_account_hash(&mut final_account_hashes, account_id, account_hash)?;
+
+            all_output_notes.extend(output_notes.iter().cloned());
+        }
+
+        let output_notes = OutputNotes::new(all_output_notes)
+            .map_err(TransactionKernelError::OutputParsingFailed)?;
+
+        Ok(AggregatedTransactionOutputs { output_notes, final_account_hashes })
+    }
+
+    /// Records `account_id`'s final hash in `final_account_hashes`, failing if another part of
+    /// the batch already reported one for the same account.
+    fn merge_final_account_hash(
+        final_account_hashes: &mut BTreeMap<AccountId, Digest>,
+        account_id: AccountId,
+        account_hash: Digest,
+    ) -> Result<(), TransactionKernelError> {
+        if final_account_hashes.insert(account_id, account_hash).is_some() {
+            return Err(TransactionKernelError::DuplicateFinalAccountHash(account_id));
+        }
+        Ok(())
+    }
+
+    /// Returns the output stack for an aggregated batch.
+    ///
+    /// Unlike [Self::build_output_stack], there is no single final account hash to place on the
+    /// stack for a batch; the per-account hashes live in
+    /// [AggregatedTransactionOutputs::final_account_hashes] instead, so that slot is left empty.
+    pub fn build_aggregate_output_stack(aggregated: &AggregatedTransactionOutputs) -> StackOutputs {
+        Self::build_output_stack(Digest::from(EMPTY_WORD), aggregated.output_notes.commitment())
+    }
+}
+
+/// The combined outputs of a batch of transactions, produced by
+/// [TransactionKernel::aggregate_outputs].
+pub struct AggregatedTransactionOutputs {
+    /// All output notes created across the batch, under one commitment.
+    pub output_notes: OutputNotes,
+    /// The final account hash reported by each account touched in the batch, keyed by
+    /// [AccountId] so that a sequencer can still verify each account's individual state
+    /// transition.
+    pub final_account_hashes: BTreeMap<AccountId, Digest>,
+}
+
+#[cfg(test)]
+mod tests {
+    use miden_objects::accounts::account_id::testing::ACCOUNT_ID_REGULAR_ACCOUNT_UPDATABLE_CODE;
+
+    use super::*;
+
+    fn test_account_id() -> AccountId {
+        AccountId::try_from(ACCOUNT_ID_REGULAR_ACCOUNT_UPDATABLE_CODE)
+            .expect("test account id constant is well-formed")
+    }
+
+    fn non_empty_digest() -> Digest {
+        Digest::from([Felt::new(1), Felt::new(0), Felt::new(0), Felt::new(0)])
+    }
+
+    /// Builds the flat advice map entry [parse_final_account_stub] expects under a final
+    /// account hash key: the account ID and nonce packed into the first word, followed by the
+    /// vault, storage and code roots as one word each.
+    fn account_stub_advice_data(account_id: AccountId, nonce: Felt) -> Vec<Felt> {
+        let mut data = Vec::with_capacity(16);
+        data.extend([Felt::from(account_id), nonce, Felt::new(0), Felt::new(0)]);
+        data.extend(Digest::from(EMPTY_WORD).as_elements());
+        data.extend(Digest::from(EMPTY_WORD).as_elements());
+        data.extend(Digest::from(EMPTY_WORD).as_elements());
+        data
+    }
+
+    /// Builds one `(StackOutputs, AdviceMap, Vec<OutputNote>)` part reporting `account_id` as
+    /// final under `final_acct_hash`, with no output notes.
+    fn empty_part_for_account(
+        account_id: AccountId,
+        final_acct_hash: Digest,
+    ) -> (StackOutputs, AdviceMap, Vec<OutputNote>) {
+        let empty_notes_hash = OutputNotes::new(Vec::new())
+            .expect("empty output notes are always valid")
+            .commitment();
+        let stack = TransactionKernel::build_output_stack(final_acct_hash, empty_notes_hash);
+
+        let mut adv_map = AdviceMap::default();
+        adv_map.insert(final_acct_hash, account_stub_advice_data(account_id, Felt::new(1)));
+
+        (stack, adv_map, Vec::new())
+    }
+
+    #[test]
+    fn sanitized_inputs_rejects_nonempty_hash_for_new_account() {
+        let result = SanitizedTransactionInputs::new(
+            test_account_id(),
+            non_empty_digest(),
+            Digest::from(EMPTY_WORD),
+            Digest::from(EMPTY_WORD),
+            &[],
+            true,
+        );
+
+        assert!(matches!(result, Err(TransactionKernelError::InvalidInitialAccountHash(_))));
+    }
+
+    #[test]
+    fn sanitized_inputs_rejects_empty_hash_for_existing_account() {
+        let result = SanitizedTransactionInputs::new(
+            test_account_id(),
+            Digest::from(EMPTY_WORD),
+            Digest::from(EMPTY_WORD),
+            Digest::from(EMPTY_WORD),
+            &[],
+            false,
+        );
+
+        assert!(matches!(result, Err(TransactionKernelError::InvalidInitialAccountHash(_))));
+    }
+
+    #[test]
+    fn sanitized_inputs_rejects_input_notes_commitment_mismatch() {
+        let result = SanitizedTransactionInputs::new(
+            test_account_id(),
+            Digest::from(EMPTY_WORD),
+            non_empty_digest(),
+            Digest::from(EMPTY_WORD),
+            &[],
+            true,
+        );
+
+        assert!(matches!(
+            result,
+            Err(TransactionKernelError::InputNotesCommitmentMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn aggregate_outputs_rejects_duplicate_final_account_hash() {
+        let account_id = test_account_id();
+        let mut final_account_hashes = BTreeMap::new();
+
+        TransactionKernel::merge_final_account_hash(
+            &mut final_account_hashes,
+            account_id,
+            Digest::from(EMPTY_WORD),
+        )
+        .expect("first report for this account should be accepted");
+
+        let result = TransactionKernel::merge_final_account_hash(
+            &mut final_account_hashes,
+            account_id,
+            non_empty_digest(),
+        );
+
+        assert!(matches!(result, Err(TransactionKernelError::DuplicateFinalAccountHash(id)) if id == account_id));
+    }
+
+    #[test]
+    fn aggregate_outputs_rejects_duplicate_account_across_parts() {
+        let account_id = test_account_id();
+        let final_acct_hash = non_empty_digest();
+
+        let parts = [
+            empty_part_for_account(account_id, final_acct_hash),
+            empty_part_for_account(account_id, final_acct_hash),
+        ];
+
+        let result = TransactionKernel::aggregate_outputs(&parts);
+
+        assert!(matches!(
+            result,
+            Err(TransactionKernelError::DuplicateFinalAccountHash(id)) if id == account_id
+        ));
+    }
+
+    #[test]
+    fn latest_kernel_verifies() {
+        TransactionKernel::verify_kernel(KernelVersion::latest())
+            .expect("the canonical transaction kernel must be well-formed");
+    }
 }